@@ -14,8 +14,7 @@ async fn main() {
     // We use this to notify the async task that the threshold has been reached
     let (tx, mut rx) = watch::channel::<()>(());
 
-    ALLOCATOR.set_threshold(100 * 1024 * 1024);
-    ALLOCATOR.set_callback(move |_| {
+    ALLOCATOR.add_threshold(100 * 1024 * 1024, move |_| {
         tx.send(()).ok();
     });
 
@@ -10,11 +10,9 @@
 //!
 //! fn main() {
 //!
-//!     // Set the threshold we care about reaching
-//!     THRESHER.set_threshold(100 * 1024 * 1024);
-//!
-//!     // Set the callback when the threshold is reached (note: may be called multiple times)
-//!     THRESHER.set_callback(|allocation| {
+//!     // Register a threshold we care about reaching, along with its callback
+//!     // (note: the callback may be called multiple times)
+//!     THRESHER.add_threshold(100 * 1024 * 1024, |allocation| {
 //!         println!("Threshold reached! Allocated: {} bytes", allocation);
 //!     });
 //!
@@ -23,18 +21,124 @@
 
 use std::{
     alloc::{GlobalAlloc, Layout},
+    cell::RefCell,
     sync::{
-        OnceLock,
-        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
 };
 
+thread_local! {
+    // A stack of the currently-active scopes on this thread, innermost last.
+    // Allocation growth is attributed to every scope on the stack.
+    static CURRENT_SCOPES: RefCell<Vec<Arc<ScopeStats>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The statistics gathered for a single [`Thresher::track_scope()`] guard.
+///
+/// These are gross figures gathered only while the owning scope is active on its
+/// thread: bytes freed after the scope has exited are not subtracted back out, so
+/// a long-lived allocation made inside a short scope still counts against it.
+#[derive(Default)]
+pub struct ScopeStats {
+    bytes_allocated: AtomicUsize,
+    num_allocations: AtomicUsize,
+}
+
+impl ScopeStats {
+    /// The number of bytes allocated while this scope was active on its thread.
+    pub fn bytes_allocated(&self) -> usize {
+        self.bytes_allocated.load(Ordering::Acquire)
+    }
+
+    /// The number of allocations made while this scope was active on its thread.
+    pub fn num_allocations(&self) -> usize {
+        self.num_allocations.load(Ordering::Acquire)
+    }
+}
+
+/// A guard returned by [`Thresher::track_scope()`]. While held, allocation growth
+/// on the current thread is attributed to its [`ScopeStats`] in addition to the
+/// allocator's global counters. Dropping it ends the scope.
+///
+/// Not `Send`: a scope only tracks allocations made on the thread that entered it.
+pub struct ScopeGuard {
+    stats: Arc<ScopeStats>,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl ScopeGuard {
+    /// The statistics gathered so far for this scope.
+    pub fn stats(&self) -> &ScopeStats {
+        &self.stats
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        CURRENT_SCOPES.with(|scopes| {
+            // `try_borrow_mut` rather than `borrow_mut`: if this runs while the
+            // stack is already mutably borrowed (e.g. a `track_scope()` on this
+            // thread is mid-`push`, reentered via an allocation that push
+            // itself triggered), silently skipping is better than panicking
+            // inside `Drop` during an allocator callback.
+            let Ok(mut scopes) = scopes.try_borrow_mut() else {
+                return;
+            };
+
+            // Find this guard's own entry by identity rather than assuming
+            // it's the last one: scopes are only nested in LIFO order by
+            // convention, and e.g. two `ScopeGuard`s held as struct fields
+            // drop in declaration order, not reverse, so a later-created
+            // scope can easily outlive an earlier one.
+            if let Some(index) = scopes.iter().position(|s| Arc::ptr_eq(s, &self.stats)) {
+                scopes.remove(index);
+            }
+        });
+    }
+}
+
+/// A point-in-time copy of a [`Thresher`]'s stats, as returned by
+/// [`Thresher::snapshot()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub current_allocated: usize,
+    pub peak_allocated: usize,
+    pub total_allocated: usize,
+    pub largest_allocation: usize,
+    pub num_allocations: usize,
+}
+
+/// A single registered watermark: a `threshold` in bytes and the `callback` to run
+/// when total allocation crosses it going upward.
+///
+/// `armed` tracks whether this level is still waiting to fire. It is disarmed the
+/// moment the callback runs, and re-armed once usage drops back below `threshold`.
+struct Level {
+    threshold: usize,
+    // `Arc` rather than `Box`: `scan_up()` needs to clone out the callbacks
+    // of the levels it just crossed and invoke them after releasing the
+    // `levels` lock, not while holding it.
+    callback: Arc<dyn Fn(usize) + Send + Sync>,
+    armed: AtomicBool,
+}
+
 /// The main allocation wrapper. [`Thresher::new()`] to wrap an existing allocator
 pub struct Thresher<A> {
     allocator: A,
     allocated: AtomicUsize,
-    threshold: AtomicUsize,
-    callback: OnceLock<Box<dyn Fn(usize) + Send + Sync>>,
+    levels: Mutex<Vec<Level>>,
+    // Cache of the lowest armed level's threshold, so the hot allocation path can
+    // tell with a single compare whether a lock-and-scan is needed at all.
+    next_up: AtomicUsize,
+    // Cache of the highest disarmed level's threshold, for the same reason on the
+    // deallocation path (re-arming levels once usage drops back below them).
+    next_down: AtomicUsize,
+    limit: AtomicUsize,
+    peak_allocated: AtomicUsize,
+    total_allocated: AtomicUsize,
+    largest_allocation: AtomicUsize,
+    num_allocations: AtomicUsize,
 }
 
 impl<A> Thresher<A> {
@@ -58,78 +162,442 @@ impl<A> Thresher<A> {
         Self {
             allocator,
             allocated: AtomicUsize::new(0),
-            threshold: AtomicUsize::new(usize::MAX),
-            callback: OnceLock::new(),
+            levels: Mutex::new(Vec::new()),
+            next_up: AtomicUsize::new(usize::MAX),
+            next_down: AtomicUsize::new(0),
+            limit: AtomicUsize::new(usize::MAX),
+            peak_allocated: AtomicUsize::new(0),
+            total_allocated: AtomicUsize::new(0),
+            largest_allocation: AtomicUsize::new(0),
+            num_allocations: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of bytes currently live, i.e. allocated but not yet freed.
+    pub fn current_allocated(&self) -> usize {
+        self.allocated.load(Ordering::Acquire)
+    }
+
+    /// The highest value [`Thresher::current_allocated()`] has reached since this
+    /// `Thresher` was created or last [`Thresher::reset()`].
+    pub fn peak_allocated(&self) -> usize {
+        self.peak_allocated.load(Ordering::Acquire)
+    }
+
+    /// The sum of every byte allocated since this `Thresher` was created or last
+    /// [`Thresher::reset()`]. Unlike [`Thresher::current_allocated()`] frees
+    /// never decrease it; only `reset()` sets it back to zero.
+    pub fn total_allocated(&self) -> usize {
+        self.total_allocated.load(Ordering::Acquire)
+    }
+
+    /// The size in bytes of the largest single allocation growth seen so far (an
+    /// `alloc`, `alloc_zeroed`, or the growing portion of a `realloc`).
+    pub fn largest_allocation(&self) -> usize {
+        self.largest_allocation.load(Ordering::Acquire)
+    }
+
+    /// The number of times allocation has grown the live byte count, i.e. the
+    /// number of `alloc`/`alloc_zeroed` calls plus growing `realloc` calls.
+    pub fn num_allocations(&self) -> usize {
+        self.num_allocations.load(Ordering::Acquire)
+    }
+
+    /// Take a point-in-time copy of every stat exposed by this `Thresher`, handy
+    /// for reading them all as one consistent-ish group rather than one call at a
+    /// time.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            current_allocated: self.current_allocated(),
+            peak_allocated: self.peak_allocated(),
+            total_allocated: self.total_allocated(),
+            largest_allocation: self.largest_allocation(),
+            num_allocations: self.num_allocations(),
         }
     }
 
-    /// Set or update the memory `threshold` in bytes.
-    /// When an allocation goes above this value, then
-    /// The callback, if set, will be executed
+    /// Remove every registered threshold level, re-arming nothing since there is
+    /// nothing left to arm.
+    pub fn clear_thresholds(&self) {
+        let mut levels = self.levels.lock().expect("levels lock poisoned");
+        levels.clear();
+        self.recompute_cache(&levels);
+    }
+
+    /// Zero every stat and clear every registered threshold level, for measuring
+    /// allocation across a specific region of code (e.g. one benchmark
+    /// iteration): call `reset()`, run the region, then read [`Thresher::snapshot()`].
+    ///
+    /// Only call this when there are no allocations outstanding that you still
+    /// care about, since `current_allocated` is zeroed along with everything
+    /// else: freeing memory allocated before the reset just clamps the live
+    /// counter at zero rather than going negative, so `current_allocated`
+    /// (and any level re-arming derived from it) will read low until new
+    /// allocations catch it back up.
     ///
-    /// If threshold is not set, or set to `usize::MAX` this disables the callback.
+    /// If you only want a fresh callback on an otherwise-unchanged set of
+    /// levels between iterations (rather than wiping every counter and
+    /// level), use [`Thresher::replace_callback()`] instead.
     /// ```rust
     /// # use std::alloc;
     /// # use thresher::Thresher;
     /// # #[global_allocator]
     /// # static THRESHER: Thresher<alloc::System> = Thresher::new(alloc::System);
     /// fn main() {
-    ///     THRESHER.set_threshold(100 * 1024 * 1024);
+    ///     THRESHER.reset();
+    ///     let _bytes = vec![0u8; 1024];
+    ///     println!("Allocated this run: {} bytes", THRESHER.total_allocated());
     /// }
     /// ```
+    pub fn reset(&self) {
+        self.allocated.store(0, Ordering::Release);
+        self.peak_allocated.store(0, Ordering::Release);
+        self.total_allocated.store(0, Ordering::Release);
+        self.largest_allocation.store(0, Ordering::Release);
+        self.num_allocations.store(0, Ordering::Release);
+        self.clear_thresholds();
+    }
+
+    /// Set a hard ceiling in bytes on top of the wrapped allocator.
+    ///
+    /// Once [`Thresher::current_allocated()`] would exceed `limit`, `alloc`,
+    /// `alloc_zeroed` and the growing portion of `realloc` stop forwarding to the
+    /// inner allocator and return a null pointer instead, exactly as if the
+    /// underlying allocator itself had run out of memory, so the standard OOM
+    /// handling (e.g. `alloc::handle_alloc_error`) kicks in.
+    ///
+    /// If limit is not set, or set to `usize::MAX`, there is no cap.
+    /// ```rust
+    /// # use std::alloc;
+    /// # use thresher::Thresher;
+    /// # #[global_allocator]
+    /// # static THRESHER: Thresher<alloc::System> = Thresher::new(alloc::System);
+    /// fn main() {
+    ///     THRESHER.set_limit(256 * 1024 * 1024);
+    /// }
+    /// ```
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Release);
+    }
+
+    /// Start tracking allocations made by the current thread for as long as the
+    /// returned [`ScopeGuard`] is alive, attributing them to their own
+    /// [`ScopeStats`] in addition to the global counters. Scopes nest: an
+    /// allocation made inside a nested scope is attributed to every scope on the
+    /// current thread's stack. Guards are matched by identity when they drop,
+    /// so they don't need to be dropped in strict LIFO order.
+    ///
+    /// This is a lightweight, approximate per-task memory report: it counts bytes
+    /// allocated while the scope is active, not bytes still live, so freeing
+    /// memory after the scope ends does not reduce its totals.
     ///
-    pub fn set_threshold(&self, threshold: usize) {
-        self.threshold.store(threshold, Ordering::Release);
+    /// ```rust
+    /// # use std::alloc;
+    /// # use thresher::Thresher;
+    /// # #[global_allocator]
+    /// # static THRESHER: Thresher<alloc::System> = Thresher::new(alloc::System);
+    /// fn main() {
+    ///     let scope = THRESHER.track_scope();
+    ///     let _bytes = vec![0u8; 1024];
+    ///     println!("Allocated in scope: {} bytes", scope.stats().bytes_allocated());
+    /// }
+    /// ```
+    pub fn track_scope(&self) -> ScopeGuard {
+        let stats = Arc::new(ScopeStats::default());
+
+        CURRENT_SCOPES.with(|scopes| {
+            // `try_borrow_mut`: growing this thread-local `Vec` for the first
+            // time can itself allocate, which reenters `commit_growth()` on
+            // this same thread and would otherwise try to borrow `scopes`
+            // again while this `push` still holds it mutably. Skipping in
+            // that case just means the reentrant allocation isn't attributed
+            // to any scope, which is harmless compared to the alternative.
+            if let Ok(mut scopes) = scopes.try_borrow_mut() {
+                scopes.push(stats.clone());
+            }
+        });
+
+        ScopeGuard {
+            stats,
+            _not_send: std::marker::PhantomData,
+        }
     }
 
-    /// Set the callback to execute when the threshold is reached.
-    /// This callback may be called multiple times if the allocation threshold is reached and then reduced.
+    /// Register a memory `threshold` in bytes along with the `callback` to run when
+    /// total allocation crosses it going upward.
+    ///
+    /// Any number of levels can be registered, each with its own callback, much
+    /// like the info/warn/error levels of a logging allocator. A level's callback
+    /// fires once each time total allocation crosses it upward, and re-arms once
+    /// usage drops back below it.
     ///
     /// As this callback happens when allocating, you need to ensure that it happens rather quickly, as to not block running code.
     ///
-    /// Panics if set more than once.
     /// ```rust
     /// # use std::alloc;
     /// # use thresher::Thresher;
     /// # #[global_allocator]
     /// # static THRESHER: Thresher<alloc::System> = Thresher::new(alloc::System);
     /// fn main() {
-    ///     THRESHER.set_callback(|allocation| {
+    ///     THRESHER.add_threshold(100 * 1024 * 1024, |allocation| {
     ///         println!("Threshold reached! Allocated: {} bytes", allocation);
     ///     });
     /// }
     /// ```
-    pub fn set_callback<F>(&self, callback: F)
+    pub fn add_threshold<F>(&self, threshold: usize, callback: F)
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        let mut levels = self.levels.lock().expect("levels lock poisoned");
+
+        levels.push(Level {
+            threshold,
+            callback: Arc::new(callback),
+            armed: AtomicBool::new(true),
+        });
+        levels.sort_by_key(|level| level.threshold);
+
+        self.recompute_cache(&levels);
+    }
+
+    /// Replace the callback of the already-registered level at `threshold`
+    /// with `callback` and re-arm it, returning `true` if such a level
+    /// existed (`false` leaves nothing changed).
+    ///
+    /// Handy for a benchmark harness that wants to keep the same threshold
+    /// across iterations but install a fresh callback each time to capture
+    /// that iteration's crossing, without clearing every other registered
+    /// level the way [`Thresher::reset()`] does.
+    /// ```rust
+    /// # use std::alloc;
+    /// # use thresher::Thresher;
+    /// # #[global_allocator]
+    /// # static THRESHER: Thresher<alloc::System> = Thresher::new(alloc::System);
+    /// fn main() {
+    ///     THRESHER.add_threshold(1024, |_| {});
+    ///     THRESHER.replace_callback(1024, |allocation| {
+    ///         println!("Iteration crossed 1024 bytes at {allocation}");
+    ///     });
+    /// }
+    /// ```
+    pub fn replace_callback<F>(&self, threshold: usize, callback: F) -> bool
     where
         F: Fn(usize) + Send + Sync + 'static,
     {
-        self.callback
-            .set(Box::new(callback))
-            .map_err(drop)
-            .expect("Callback is already registered");
-    }
-
-    fn maybe_callback(&self, allocation_size: usize) {
-        let threshold = self.threshold.load(Ordering::Acquire);
-        let old_allocated = self.allocated.fetch_add(allocation_size, Ordering::Release);
-        let new_allocated = old_allocated + allocation_size;
-
-        // only execute call back when we've passed the threshold
-        if new_allocated >= threshold
-            && old_allocated < threshold
-            && let Some(cb) = self.callback.get()
-        {
-            cb(new_allocated);
+        let mut levels = self.levels.lock().expect("levels lock poisoned");
+
+        let Some(level) = levels.iter_mut().find(|level| level.threshold == threshold) else {
+            return false;
+        };
+
+        level.callback = Arc::new(callback);
+        level.armed.store(true, Ordering::Release);
+
+        self.recompute_cache(&levels);
+        true
+    }
+
+    /// Remove the level registered at `threshold` and return its callback, or
+    /// `None` if no level with that threshold is registered.
+    pub fn take_callback(&self, threshold: usize) -> Option<Arc<dyn Fn(usize) + Send + Sync>> {
+        let mut levels = self.levels.lock().expect("levels lock poisoned");
+
+        let index = levels
+            .iter()
+            .position(|level| level.threshold == threshold)?;
+        let level = levels.remove(index);
+
+        self.recompute_cache(&levels);
+        Some(level.callback)
+    }
+
+    /// Recompute the `next_up`/`next_down` caches from the current set of levels.
+    /// Must be called with `levels` held.
+    fn recompute_cache(&self, levels: &[Level]) {
+        let mut next_up = usize::MAX;
+        let mut next_down = 0;
+
+        for level in levels {
+            if level.armed.load(Ordering::Acquire) {
+                next_up = next_up.min(level.threshold);
+            } else {
+                next_down = next_down.max(level.threshold);
+            }
+        }
+
+        self.next_up.store(next_up, Ordering::Release);
+        self.next_down.store(next_down, Ordering::Release);
+    }
+
+    /// Reserve `allocation_size` bytes against the configured limit, using a CAS
+    /// loop so two racing threads can't both pass the check. Returns the
+    /// `(old_allocated, new_allocated)` pair on success, or `None` without
+    /// touching `allocated` if the reservation would exceed the limit.
+    ///
+    /// The reservation must be followed by either [`Thresher::commit_growth()`]
+    /// once the inner allocator has actually granted the memory, or
+    /// [`Thresher::unreserve()`] if it didn't.
+    fn reserve(&self, allocation_size: usize) -> Option<(usize, usize)> {
+        let limit = self.limit.load(Ordering::Acquire);
+        let mut old_allocated = self.allocated.load(Ordering::Acquire);
+
+        let new_allocated = loop {
+            let new_allocated = old_allocated + allocation_size;
+
+            if new_allocated > limit {
+                return None;
+            }
+
+            match self.allocated.compare_exchange_weak(
+                old_allocated,
+                new_allocated,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break new_allocated,
+                Err(actual) => old_allocated = actual,
+            }
+        };
+
+        Some((old_allocated, new_allocated))
+    }
+
+    /// Release a reservation taken by [`Thresher::reserve()`] whose allocation
+    /// the inner allocator did not actually grant.
+    fn unreserve(&self, allocation_size: usize) {
+        self.allocated.fetch_sub(allocation_size, Ordering::Release);
+    }
+
+    /// Record a growth of `allocation_size` bytes that the inner allocator has
+    /// actually granted: update the lifetime stats and fire any threshold
+    /// callbacks now crossed. `old_allocated`/`new_allocated` are the pair
+    /// returned by the [`Thresher::reserve()`] call that this growth corresponds
+    /// to.
+    fn commit_growth(&self, old_allocated: usize, new_allocated: usize, allocation_size: usize) {
+        self.total_allocated
+            .fetch_add(allocation_size, Ordering::Relaxed);
+        self.num_allocations.fetch_add(1, Ordering::Relaxed);
+        self.largest_allocation
+            .fetch_max(allocation_size, Ordering::Relaxed);
+        self.peak_allocated.fetch_max(new_allocated, Ordering::Relaxed);
+
+        CURRENT_SCOPES.with(|scopes| {
+            // See the matching comment in `track_scope()`: this can reenter
+            // while `scopes` is already mutably borrowed by the `push` in
+            // `track_scope()` on this thread, so skip rather than panic.
+            let Ok(scopes) = scopes.try_borrow() else {
+                return;
+            };
+
+            for scope in scopes.iter() {
+                scope
+                    .bytes_allocated
+                    .fetch_add(allocation_size, Ordering::Relaxed);
+                scope.num_allocations.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        // Cheap common-case check: only take the lock once usage has reached
+        // the lowest armed level. An armed level at or below `old_allocated`
+        // (e.g. one registered after usage had already passed it) keeps this
+        // true, so every allocation goes through `scan_up()` until usage drops
+        // back below that level.
+        if new_allocated >= self.next_up.load(Ordering::Acquire) {
+            self.scan_up(old_allocated, new_allocated);
+        }
+    }
+
+    /// Fire the callbacks of every level just crossed going upward.
+    ///
+    /// The callbacks themselves run after the `levels` lock has been
+    /// released, not while it's held: a callback is user code, and the repo
+    /// can't assume it won't allocate (the documented `println!` example
+    /// does). An allocation inside the callback reenters this allocator on
+    /// the same thread, and `std::sync::Mutex` isn't reentrant, so calling a
+    /// callback with the lock held would deadlock the thread against itself
+    /// the moment that happens -- and if the callback panicked instead, it
+    /// would poison the lock for every `alloc`/`dealloc` afterwards. The
+    /// cache is also recomputed before any callback runs, so a callback that
+    /// allocates sees an up-to-date `next_up`/`next_down` rather than the
+    /// stale, just-crossed threshold.
+    fn scan_up(&self, old_allocated: usize, new_allocated: usize) {
+        let crossed = {
+            let levels = self.levels.lock().expect("levels lock poisoned");
+
+            let crossed: Vec<_> = levels
+                .iter()
+                .filter(|level| {
+                    level.threshold > old_allocated
+                        && level.threshold <= new_allocated
+                        && level.armed.swap(false, Ordering::AcqRel)
+                })
+                .map(|level| level.callback.clone())
+                .collect();
+
+            self.recompute_cache(&levels);
+            crossed
+        };
+
+        for callback in crossed {
+            callback(new_allocated);
+        }
+    }
+
+    /// Account for a shrink of `size` bytes, re-arming any levels usage has
+    /// dropped back below.
+    ///
+    /// `size` is saturated against the live counter rather than subtracted
+    /// outright: freeing more than `current_allocated()` still thinks is
+    /// live (e.g. after a [`Thresher::reset()`] zeroed it out from under
+    /// allocations made before the reset) clamps to zero instead of
+    /// underflowing.
+    fn shrink(&self, size: usize) {
+        let mut old_allocated = self.allocated.load(Ordering::Acquire);
+
+        let new_allocated = loop {
+            let new_allocated = old_allocated.saturating_sub(size);
+
+            match self.allocated.compare_exchange_weak(
+                old_allocated,
+                new_allocated,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break new_allocated,
+                Err(actual) => old_allocated = actual,
+            }
+        };
+
+        if new_allocated < self.next_down.load(Ordering::Acquire) {
+            self.scan_down(old_allocated, new_allocated);
         }
     }
+
+    fn scan_down(&self, old_allocated: usize, new_allocated: usize) {
+        let levels = self.levels.lock().expect("levels lock poisoned");
+
+        for level in levels.iter() {
+            if level.threshold <= old_allocated && level.threshold > new_allocated {
+                level.armed.store(true, Ordering::Release);
+            }
+        }
+
+        self.recompute_cache(&levels);
+    }
 }
 
 unsafe impl<A: GlobalAlloc> GlobalAlloc for Thresher<A> {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let Some((old_allocated, new_allocated)) = self.reserve(layout.size()) else {
+            return std::ptr::null_mut();
+        };
+
         let ptr = unsafe { self.allocator.alloc(layout) };
 
-        if !ptr.is_null() {
-            self.maybe_callback(layout.size());
+        if ptr.is_null() {
+            self.unreserve(layout.size());
+        } else {
+            self.commit_growth(old_allocated, new_allocated, layout.size());
         }
 
         ptr
@@ -137,38 +605,55 @@ unsafe impl<A: GlobalAlloc> GlobalAlloc for Thresher<A> {
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         unsafe { self.allocator.dealloc(ptr, layout) };
-        let size = layout.size();
-        self.allocated.fetch_sub(size, Ordering::Release);
+        self.shrink(layout.size());
     }
 
     unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let Some((old_allocated, new_allocated)) = self.reserve(layout.size()) else {
+            return std::ptr::null_mut();
+        };
+
         let ptr = unsafe { self.allocator.alloc_zeroed(layout) };
 
-        if !ptr.is_null() {
-            self.maybe_callback(layout.size());
+        if ptr.is_null() {
+            self.unreserve(layout.size());
+        } else {
+            self.commit_growth(old_allocated, new_allocated, layout.size());
         }
 
         ptr
     }
 
     unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, size: usize) -> *mut u8 {
-        let new_ptr = unsafe { self.allocator.realloc(ptr, old_layout, size) };
+        let old_size = old_layout.size();
+        let new_size =
+            unsafe { Layout::from_size_align_unchecked(size, old_layout.align()) }.size();
+
+        if new_size > old_size {
+            let allocation_size = new_size - old_size;
 
-        if !new_ptr.is_null() {
-            let old_size = old_layout.size();
-            let new_size =
-                unsafe { Layout::from_size_align_unchecked(size, old_layout.align()) }.size();
+            let Some((old_allocated, new_allocated)) = self.reserve(allocation_size) else {
+                return std::ptr::null_mut();
+            };
 
-            if new_size > old_size {
-                let allocation_size = new_size - old_size;
-                self.maybe_callback(allocation_size);
+            let new_ptr = unsafe { self.allocator.realloc(ptr, old_layout, size) };
+
+            if new_ptr.is_null() {
+                self.unreserve(allocation_size);
             } else {
-                self.allocated
-                    .fetch_sub(old_size - new_size, Ordering::Release);
+                self.commit_growth(old_allocated, new_allocated, allocation_size);
+            }
+
+            new_ptr
+        } else {
+            let new_ptr = unsafe { self.allocator.realloc(ptr, old_layout, size) };
+
+            if !new_ptr.is_null() {
+                self.shrink(old_size - new_size);
             }
-        }
 
-        new_ptr
+            new_ptr
+        }
     }
 }
 
@@ -190,8 +675,7 @@ mod tests {
         let flag = Arc::new(AtomicBool::new(false));
         let cb_flag = flag.clone();
 
-        ALLOCATOR.set_threshold(1024 * 1024);
-        ALLOCATOR.set_callback(move |_| {
+        ALLOCATOR.add_threshold(1024 * 1024, move |_| {
             cb_flag.store(true, Ordering::Release);
         });
 
@@ -199,4 +683,199 @@ mod tests {
         let _bytes = vec![0u8; 1024 * 1024];
         assert!(flag.load(Ordering::Acquire));
     }
+
+    #[test]
+    fn multiple_levels() {
+        let info_hits = Arc::new(AtomicUsize::new(0));
+        let warn_hits = Arc::new(AtomicUsize::new(0));
+
+        let info_cb = info_hits.clone();
+        let warn_cb = warn_hits.clone();
+
+        ALLOCATOR.add_threshold(2 * 1024 * 1024, move |_| {
+            info_cb.fetch_add(1, Ordering::Release);
+        });
+        ALLOCATOR.add_threshold(3 * 1024 * 1024, move |_| {
+            warn_cb.fetch_add(1, Ordering::Release);
+        });
+
+        let _bytes = vec![0u8; 3 * 1024 * 1024 + 1];
+        assert_eq!(info_hits.load(Ordering::Acquire), 1);
+        assert_eq!(warn_hits.load(Ordering::Acquire), 1);
+    }
+
+    #[test]
+    fn stats() {
+        let allocations_before = ALLOCATOR.num_allocations();
+        let total_before = ALLOCATOR.total_allocated();
+
+        let bytes = vec![0u8; 5 * 1024 * 1024];
+
+        assert!(ALLOCATOR.current_allocated() >= 5 * 1024 * 1024);
+        assert!(ALLOCATOR.peak_allocated() >= ALLOCATOR.current_allocated());
+        assert!(ALLOCATOR.total_allocated() >= total_before + 5 * 1024 * 1024);
+        assert!(ALLOCATOR.largest_allocation() >= 5 * 1024 * 1024);
+        assert!(ALLOCATOR.num_allocations() > allocations_before);
+
+        drop(bytes);
+    }
+
+    #[test]
+    fn limit() {
+        let limit = ALLOCATOR.current_allocated() + 1024 * 1024;
+        ALLOCATOR.set_limit(limit);
+
+        let allocated_before = ALLOCATOR.current_allocated();
+        let total_before = ALLOCATOR.total_allocated();
+        let allocations_before = ALLOCATOR.num_allocations();
+
+        let layout = Layout::array::<u8>(4 * 1024 * 1024).unwrap();
+        let ptr = unsafe { ALLOCATOR.alloc(layout) };
+        assert!(ptr.is_null());
+        assert!(ALLOCATOR.current_allocated() <= limit);
+
+        // A rejected allocation must never touch the live counter or the
+        // lifetime stats: there's nothing to roll back because nothing was
+        // ever granted.
+        assert_eq!(ALLOCATOR.current_allocated(), allocated_before);
+        assert_eq!(ALLOCATOR.total_allocated(), total_before);
+        assert_eq!(ALLOCATOR.num_allocations(), allocations_before);
+
+        ALLOCATOR.set_limit(usize::MAX);
+    }
+
+    #[test]
+    fn scope() {
+        let scope = ALLOCATOR.track_scope();
+
+        let _bytes = vec![0u8; 2 * 1024 * 1024];
+        assert!(scope.stats().bytes_allocated() >= 2 * 1024 * 1024);
+        assert!(scope.stats().num_allocations() >= 1);
+
+        let stats = scope.stats.clone();
+        drop(scope);
+
+        // Allocations after the scope has ended are no longer attributed to it.
+        let bytes_at_exit = stats.bytes_allocated();
+        let _more_bytes = vec![0u8; 1024 * 1024];
+        assert_eq!(stats.bytes_allocated(), bytes_at_exit);
+    }
+
+    #[test]
+    fn scope_not_inflated_by_rejected_allocation() {
+        let limit = ALLOCATOR.current_allocated() + 1024 * 1024;
+        ALLOCATOR.set_limit(limit);
+
+        let scope = ALLOCATOR.track_scope();
+
+        let layout = Layout::array::<u8>(4 * 1024 * 1024).unwrap();
+        let ptr = unsafe { ALLOCATOR.alloc(layout) };
+        assert!(ptr.is_null());
+
+        // A rejected allocation granted nothing, so the active scope must not
+        // be charged for it either.
+        assert_eq!(scope.stats().bytes_allocated(), 0);
+        assert_eq!(scope.stats().num_allocations(), 0);
+
+        ALLOCATOR.set_limit(usize::MAX);
+    }
+
+    #[test]
+    fn scopes_survive_out_of_order_drop() {
+        struct Holder {
+            outer: ScopeGuard,
+            inner: ScopeGuard,
+        }
+
+        // Struct fields drop in declaration order, so `outer` drops here
+        // while `inner` is still alive: the opposite of the usual
+        // LIFO `track_scope()` nesting.
+        let holder = Holder {
+            outer: ALLOCATOR.track_scope(),
+            inner: ALLOCATOR.track_scope(),
+        };
+
+        let _bytes = vec![0u8; 1024 * 1024];
+        assert!(holder.outer.stats().bytes_allocated() >= 1024 * 1024);
+        assert!(holder.inner.stats().bytes_allocated() >= 1024 * 1024);
+
+        let outer_stats = holder.outer.stats.clone();
+        let inner_stats = holder.inner.stats.clone();
+        drop(holder);
+
+        // Both scopes must be gone from the stack; allocations afterwards
+        // aren't attributed to either one.
+        let outer_at_exit = outer_stats.bytes_allocated();
+        let inner_at_exit = inner_stats.bytes_allocated();
+        let _more_bytes = vec![0u8; 1024 * 1024];
+        assert_eq!(outer_stats.bytes_allocated(), outer_at_exit);
+        assert_eq!(inner_stats.bytes_allocated(), inner_at_exit);
+    }
+
+    #[test]
+    fn reset_and_snapshot() {
+        // A standalone instance (not the global allocator) so resetting its live
+        // counter can't interfere with other tests sharing `ALLOCATOR`.
+        let thresher = Thresher::new(alloc::System);
+        thresher.add_threshold(1024, |_| {});
+
+        let layout = Layout::array::<u8>(1024 * 1024).unwrap();
+        let ptr = unsafe { thresher.alloc(layout) };
+        assert!(!ptr.is_null());
+
+        thresher.reset();
+        assert_eq!(thresher.snapshot(), Snapshot::default());
+
+        let ptr2 = unsafe { thresher.alloc(layout) };
+        assert!(!ptr2.is_null());
+
+        let snapshot = thresher.snapshot();
+        assert_eq!(snapshot.total_allocated, 1024 * 1024);
+        assert_eq!(snapshot.total_allocated, thresher.total_allocated());
+
+        unsafe { thresher.dealloc(ptr2, layout) };
+        unsafe { alloc::System.dealloc(ptr, layout) };
+    }
+
+    #[test]
+    fn reset_then_dealloc_does_not_underflow() {
+        let thresher = Thresher::new(alloc::System);
+
+        let layout = Layout::array::<u8>(1024).unwrap();
+        let ptr = unsafe { thresher.alloc(layout) };
+        assert!(!ptr.is_null());
+
+        // Simulates freeing an allocation made before a `reset()`: the live
+        // counter has been zeroed out from under it, so this must clamp to
+        // zero rather than underflowing.
+        thresher.reset();
+        unsafe { thresher.dealloc(ptr, layout) };
+
+        assert_eq!(thresher.current_allocated(), 0);
+    }
+
+    #[test]
+    fn replace_and_take_callback() {
+        let thresher = Thresher::new(alloc::System);
+
+        assert!(!thresher.replace_callback(1024, |_| {}));
+
+        thresher.add_threshold(1024, |_| {});
+
+        let fired = Arc::new(AtomicBool::new(false));
+        let fired_clone = fired.clone();
+        assert!(thresher.replace_callback(1024, move |_| {
+            fired_clone.store(true, Ordering::Release);
+        }));
+
+        let layout = Layout::array::<u8>(2048).unwrap();
+        let ptr = unsafe { thresher.alloc(layout) };
+        assert!(!ptr.is_null());
+        assert!(fired.load(Ordering::Acquire));
+
+        assert!(thresher.take_callback(1024).is_some());
+        assert!(thresher.take_callback(1024).is_none());
+
+        unsafe { thresher.dealloc(ptr, layout) };
+    }
 }